@@ -0,0 +1,37 @@
+use std::fmt;
+
+use dobby_rs::DobbyHookError;
+
+/// Errors that can occur when installing a hook.
+#[derive(Debug)]
+pub enum Error {
+    /// The underlying `dobby-rs` hook operation failed.
+    Hook(DobbyHookError),
+    /// [`resolve_symbol`](dobby_rs::resolve_symbol) could not find `symbol` in `module`.
+    SymbolNotFound {
+        module: Option<String>,
+        symbol: String,
+    },
+}
+
+impl From<DobbyHookError> for Error {
+    fn from(error: DobbyHookError) -> Self {
+        Error::Hook(error)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Hook(error) => write!(f, "hook error: {error:?}"),
+            Error::SymbolNotFound { module: Some(module), symbol } => {
+                write!(f, "symbol `{symbol}` not found in module `{module}`")
+            }
+            Error::SymbolNotFound { module: None, symbol } => {
+                write!(f, "symbol `{symbol}` not found")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
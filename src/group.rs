@@ -0,0 +1,70 @@
+use crate::hook::Toggle;
+use crate::{install_hook, Error};
+
+type PendingHook = Box<dyn FnOnce() -> Result<Box<dyn Toggle>, Error>>;
+
+/// A builder for installing several hooks as a single transaction.
+///
+/// Queue hooks with [`HookGroup::add`], then install them all with [`HookGroup::commit`]. If any
+/// hook in the group fails to install, every hook already installed by that `commit` call is
+/// unhooked before the error is returned, so a mod's hooks either all end up installed or none do
+/// instead of leaving the process half-patched. Once committed, [`HookGroup::disable_all`] and
+/// [`HookGroup::enable_all`] toggle the whole group together.
+#[derive(Default)]
+pub struct HookGroup {
+    pending: Vec<PendingHook>,
+    installed: Vec<Box<dyn Toggle>>,
+}
+
+impl HookGroup {
+    /// Creates an empty group.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a hook redirecting `target` to `replacement`, to be installed when [`commit`](Self::commit)
+    /// is called. Returns `self` so calls can be chained.
+    ///
+    /// # Safety
+    /// Hooking is inherently unsafe. It is up to the caller to ensure that the signatures of
+    /// `target` and `replacement` are truly compatible.
+    pub unsafe fn add<F: Copy + 'static>(mut self, target: F, replacement: F) -> Self {
+        self.pending.push(Box::new(move || {
+            unsafe { install_hook(target, replacement) }.map(|hook| Box::new(hook) as Box<dyn Toggle>)
+        }));
+
+        self
+    }
+
+    /// Installs every queued hook in order.
+    ///
+    /// If a hook fails to install, every hook already installed by this call is unhooked (by
+    /// dropping it, restoring its target) before the error is returned.
+    pub fn commit(mut self) -> Result<Self, Error> {
+        for pending in self.pending.drain(..) {
+            match pending() {
+                Ok(hook) => self.installed.push(hook),
+                Err(error) => {
+                    self.installed.clear();
+                    return Err(error);
+                }
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Disables every hook installed in this group. See [`Hook::disable`](crate::Hook::disable).
+    pub fn disable_all(&mut self) {
+        for hook in &mut self.installed {
+            hook.disable();
+        }
+    }
+
+    /// Enables every hook installed in this group. See [`Hook::enable`](crate::Hook::enable).
+    pub fn enable_all(&mut self) {
+        for hook in &mut self.installed {
+            hook.enable();
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use std::mem;
+
+use dobby_rs::Address;
+
+use crate::Error;
+
+/// Number of bytes at the start of a hooked function that are snapshotted before and after dobby
+/// writes its detour jump there.
+///
+/// This only needs to be large enough to cover the patched prologue so that [`Hook::disable`] and
+/// [`Hook::enable`] have the full region to snapshot and restore; it does not constrain dobby's
+/// own patching.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+const PATCHED_REGION_LEN: usize = 16;
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+const PATCHED_REGION_LEN: usize = 32;
+
+/// An installed hook that owns the patch it made to `target` and can be re-enabled, disabled, or
+/// dropped to restore the original function.
+///
+/// This is returned by [`install_hook`] (and the [`hook!`](crate::hook) macro) instead of a bare
+/// trampoline, so a hook's lifetime can be tied to whatever feature installed it rather than
+/// living for the rest of the process.
+pub struct Hook<F: Copy> {
+    target: Address,
+    trampoline: F,
+    /// Bytes originally at `target`, before it was patched.
+    original_bytes: Vec<u8>,
+    /// Bytes dobby wrote at `target` to redirect it to the replacement.
+    patched_bytes: Vec<u8>,
+    enabled: bool,
+}
+
+impl<F: Copy> Hook<F> {
+    /// The trampoline function pointer that can be used to call the original implementation of
+    /// the hooked function, bypassing the redirection to the replacement.
+    pub fn trampoline(&self) -> F {
+        self.trampoline
+    }
+
+    /// Returns `true` if `target` is currently redirected to the replacement.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Restores `target`'s original bytes, undoing the redirection until [`Hook::enable`] is
+    /// called again. Does nothing if the hook is already disabled.
+    ///
+    /// This is called from [`Drop`], so a failure to change `target`'s memory protection is
+    /// logged to stderr rather than panicking; on failure the hook is left enabled.
+    pub fn disable(&mut self) {
+        if self.enabled && unsafe { write_region(self.target, &self.original_bytes) } {
+            self.enabled = false;
+        }
+    }
+
+    /// Writes the detour jump back to `target`, resuming redirection to the replacement. Does
+    /// nothing if the hook is already enabled.
+    ///
+    /// A failure to change `target`'s memory protection is logged to stderr rather than
+    /// panicking; on failure the hook is left disabled.
+    pub fn enable(&mut self) {
+        if !self.enabled && unsafe { write_region(self.target, &self.patched_bytes) } {
+            self.enabled = true;
+        }
+    }
+
+    /// Disables the hook if it is enabled, or enables it if it is disabled.
+    pub fn toggle(&mut self) {
+        if self.enabled {
+            self.disable();
+        } else {
+            self.enable();
+        }
+    }
+}
+
+impl<F: Copy> Drop for Hook<F> {
+    fn drop(&mut self) {
+        self.disable();
+    }
+}
+
+/// Object-safe subset of [`Hook`]'s toggling behaviour.
+///
+/// `Hook<F>` is generic over the hooked function's type, which makes it impossible to store hooks
+/// of different signatures in the same collection. [`HookGroup`](crate::HookGroup) manages its
+/// hooks as `Box<dyn Toggle>` instead, since none of this behaviour depends on `F`.
+pub trait Toggle {
+    /// See [`Hook::disable`].
+    fn disable(&mut self);
+    /// See [`Hook::enable`].
+    fn enable(&mut self);
+    /// See [`Hook::is_enabled`].
+    fn is_enabled(&self) -> bool;
+}
+
+impl<F: Copy> Toggle for Hook<F> {
+    fn disable(&mut self) {
+        Hook::disable(self)
+    }
+
+    fn enable(&mut self) {
+        Hook::enable(self)
+    }
+
+    fn is_enabled(&self) -> bool {
+        Hook::is_enabled(self)
+    }
+}
+
+/// Copies `bytes` into the memory at `address` and flushes the instruction cache for that region
+/// so the CPU observes the new code.
+///
+/// `target`'s code is normally mapped read-execute, so this temporarily makes the region writable
+/// (dobby only does this for the duration of its own `hook()` call) and restores the original
+/// protection once the copy is done.
+///
+/// Returns `false` without writing anything if the region's protection couldn't be changed. This
+/// is reachable from [`Hook`]'s `Drop` impl, where panicking would abort the process on an
+/// in-flight unwind, so callers are expected to handle failure rather than `expect` it.
+unsafe fn write_region(address: Address, bytes: &[u8]) -> bool {
+    let guard = match region::protect_with_handle(
+        address as *const (),
+        bytes.len(),
+        region::Protection::READ_WRITE_EXECUTE,
+    ) {
+        Ok(guard) => guard,
+        Err(error) => {
+            eprintln!("hlhook: failed to make hooked region writable: {error}");
+            return false;
+        }
+    };
+
+    std::ptr::copy_nonoverlapping(bytes.as_ptr(), address as *mut u8, bytes.len());
+    flush_instruction_cache(address, bytes.len());
+
+    drop(guard);
+    true
+}
+
+/// Reads `len` bytes starting at `address` into an owned buffer.
+unsafe fn read_region(address: Address, len: usize) -> Vec<u8> {
+    std::slice::from_raw_parts(address as *const u8, len).to_vec()
+}
+
+#[cfg(target_arch = "aarch64")]
+unsafe fn flush_instruction_cache(address: Address, len: usize) {
+    // dobby's patch only takes effect once the instruction cache has been invalidated for the
+    // patched range; x86 keeps its instruction/data caches coherent with writes, but aarch64
+    // requires explicit cache maintenance.
+    const CACHE_LINE: usize = 64;
+
+    let mut line = (address as usize) & !(CACHE_LINE - 1);
+    let end = address as usize + len;
+
+    while line < end {
+        std::arch::asm!("dc cvau, {0}", "ic ivau, {0}", in(reg) line);
+        line += CACHE_LINE;
+    }
+
+    std::arch::asm!("dsb ish", "isb");
+}
+
+#[cfg(not(target_arch = "aarch64"))]
+unsafe fn flush_instruction_cache(_address: Address, _len: usize) {
+    // x86(_64) maintains instruction/data cache coherency for self-modifying code, so there is
+    // nothing to flush here.
+}
+
+/// Modifies `target`'s implementation such that it redirects to `replacement`, returning a
+/// [`Hook`] that can be used to call the original implementation, temporarily disable the
+/// redirection, or restore `target` entirely by dropping it.
+///
+/// # Safety
+/// Hooking is inherently unsafe. It is up to the caller to ensure that the signatures of `target`
+/// and `replacement` are truly compatible.
+pub unsafe fn install_hook<F: Copy>(target: F, replacement: F) -> Result<Hook<F>, Error> {
+    // Our function only enforces that the target and replacement have the same type for safety,
+    // but when actually hooking we erase the type information and use raw addresses.
+    let target_addr: Address = mem::transmute_copy(&target);
+    let replacement_addr: Address = mem::transmute_copy(&replacement);
+
+    let original_bytes = read_region(target_addr, PATCHED_REGION_LEN);
+
+    let trampoline_addr = dobby_rs::hook(target_addr, replacement_addr)?;
+
+    let patched_bytes = read_region(target_addr, PATCHED_REGION_LEN);
+
+    // Add back pseudo-type-safety by returning a function pointer matching `target` and
+    // `replacement` instead of the raw trampoline address.
+    let trampoline: F = mem::transmute_copy(&trampoline_addr);
+
+    Ok(Hook {
+        target: target_addr,
+        trampoline,
+        original_bytes,
+        patched_bytes,
+        enabled: true,
+    })
+}
@@ -25,7 +25,9 @@
 //!     orig_val + 1
 //! };
 //!
-//! unsafe { hook!(fn() -> u8, ORIGINAL, target, add_one) }.expect("hook failed");
+//! // Keep the `Hook` alive for as long as the redirection should stay installed; dropping it
+//! // restores `target`.
+//! let _hook = unsafe { hook!(fn() -> u8, ORIGINAL, target, add_one) }.expect("hook failed");
 //!
 //! // ...
 //!
@@ -33,30 +35,22 @@
 //! assert_eq!(value, 11);
 //! ```
 
-use dobby_rs::DobbyHookError;
+mod error;
+mod group;
+mod hook;
+mod symbol;
+mod trampoline;
 
-/// Modifies `target`'s implementation such that it redirects to `replacement`. On success, this
-/// function will return `Ok` with a trampoline function pointer that can be used from anywhere to
-/// call the original implementation of `target` (bypassing the redirection to `replacement`).
-///
-/// # Safety
-/// Hooking is inherently unsafe. It is up to the caller to ensure that the signatures of `target`
-/// and `replacement` are truly compatible.
-pub unsafe fn install_hook<F: Copy>(target: F, replacement: F) -> Result<F, DobbyHookError> {
-    // Our function only enforces that the target and replacement have the same type for safety,
-    // but when actually hooking we erase the type information and use raw addresses.
-    let target_addr: dobby_rs::Address = std::mem::transmute_copy(&target);
-    let replacement_addr: dobby_rs::Address = std::mem::transmute_copy(&replacement);
-
-    let trampoline_addr = dobby_rs::hook(target_addr, replacement_addr)?;
-
-    // Add back pseudo-type-safety by returning a function pointer matching `target` and
-    // `replacement` instead of the raw trampoline address.
-    Ok(std::mem::transmute_copy(&trampoline_addr))
-}
+pub use error::Error;
+pub use group::HookGroup;
+pub use hook::{install_hook, Hook, Toggle};
+pub use symbol::install_hook_symbol;
+pub use trampoline::Trampoline;
 
 /// Wraps `install_hook`, casting both function pointers to the same type. If a trampoline variable
-/// is provided, it will be set to the trampoline function pointer after hooking.
+/// is provided, it will be set to the trampoline function pointer after hooking. Either way, the
+/// installed [`Hook`] is returned so it can be disabled, re-enabled, or kept alive to hold the
+/// redirection in place.
 ///
 /// # Example
 /// Here we hook `target` and add `1` to the return value.
@@ -76,7 +70,9 @@ pub unsafe fn install_hook<F: Copy>(target: F, replacement: F) -> Result<F, Dobb
 ///     orig_val + 1
 /// };
 ///
-/// unsafe { hook!(fn() -> u8, ORIGINAL, target, add_one) }.expect("hook failed");
+/// // Keep the `Hook` alive for as long as the redirection should stay installed; dropping it
+/// // restores `target`.
+/// let _hook = unsafe { hook!(fn() -> u8, ORIGINAL, target, add_one) }.expect("hook failed");
 ///
 /// // ...
 ///
@@ -90,18 +86,145 @@ macro_rules! hook {
     };
 
     ($t:ty, $trampoline:ident, $target:expr, $replacement:expr) => {
-        install_hook($target as $t, $replacement as $t).map(|t_ptr| {
-            $trampoline = Some(t_ptr);
+        install_hook($target as $t, $replacement as $t).map(|hook| {
+            $trampoline.store(hook.trampoline());
+            hook
         })
     };
 }
 
+/// Like [`hook!`], but resolves the target by symbol name (via [`install_hook_symbol`]) instead of
+/// taking a function pointer directly. Useful for hooking functions that exist in another module
+/// but aren't linked against.
+///
+/// `$module` is passed straight through to [`install_hook_symbol`], so pass `Some("libfoo.so")` to
+/// search a specific module or `None` to search the main executable/all loaded modules.
+///
+/// # Example
+/// ```rust,no_run
+/// make_trampoline!(unsafe extern "C" fn() -> u8, ORIGINAL);
+///
+/// // Must be an `extern "C" fn`: closures only convert to Rust-ABI function pointers.
+/// extern "C" fn steal_one() -> u8 {
+///     unsafe { get_trampoline!(ORIGINAL)() - 1 }
+/// }
+///
+/// let _hook = unsafe {
+///     hook_symbol!(
+///         extern "C" fn() -> u8,
+///         ORIGINAL,
+///         Some("libtarget.so"),
+///         "target_function",
+///         steal_one
+///     )
+/// }
+/// .expect("hook failed");
+/// ```
+#[macro_export]
+macro_rules! hook_symbol {
+    ($t:ty, $module:expr, $symbol:expr, $replacement:expr) => {
+        install_hook_symbol($module, $symbol, $replacement as $t)
+    };
+
+    ($t:ty, $trampoline:ident, $module:expr, $symbol:expr, $replacement:expr) => {
+        install_hook_symbol($module, $symbol, $replacement as $t).map(|hook| {
+            $trampoline.store(hook.trampoline());
+            hook
+        })
+    };
+}
+
+/// Like [`hook!`], but the replacement is a closure that may capture state, instead of being
+/// limited to a bare function pointer.
+///
+/// This works by generating a C-ABI thunk item alongside a static cell holding the boxed closure.
+/// The thunk loads the closure from the cell and forwards the arguments to it, so it can be
+/// installed as the hook's replacement in place of the closure itself. The generated thunk is
+/// always `unsafe extern "C" fn`, so `$t` must be declared `unsafe extern "C" fn` too (`as` casts
+/// can add `unsafe` to a fn pointer type but never remove it, so a safe `$t` fails to compile).
+///
+/// Only one live closure per generated thunk is supported: each `hook_closure!` invocation
+/// generates its own thunk and cell, and executing that same invocation again (for example from a
+/// function called more than once) replaces the previously stored closure. Concurrent calls into
+/// the thunk while that replacement happens are not synchronized against it.
+///
+/// # Example
+/// ```rust,no_run
+/// make_trampoline!(unsafe extern "C" fn(i32) -> i32, ORIGINAL);
+///
+/// extern "C" fn target(x: i32) -> i32 {
+///     x
+/// }
+///
+/// let offset = 1;
+///
+/// let _hook = unsafe {
+///     hook_closure!(
+///         unsafe extern "C" fn(i32) -> i32,
+///         ORIGINAL,
+///         target,
+///         move |x: i32| -> i32 { unsafe { get_trampoline!(ORIGINAL)(x) } + offset }
+///     )
+/// }
+/// .expect("hook failed");
+/// ```
+#[macro_export]
+macro_rules! hook_closure {
+    (
+        $t:ty,
+        $trampoline:ident,
+        $target:expr,
+        move |$($arg:ident : $arg_ty:ty),* $(,)?| -> $ret:ty $body:block
+    ) => {{
+        mod $trampoline {
+            #![allow(non_snake_case)]
+
+            pub(super) type BoxedClosure = ::std::boxed::Box<dyn FnMut($($arg_ty),*) -> $ret>;
+
+            static CLOSURE: ::std::sync::atomic::AtomicPtr<()> =
+                ::std::sync::atomic::AtomicPtr::new(::std::ptr::null_mut());
+
+            /// Stores `closure`, dropping whatever closure was previously stored here.
+            pub(super) fn store(closure: BoxedClosure) {
+                let raw = ::std::boxed::Box::into_raw(::std::boxed::Box::new(closure));
+                let previous = CLOSURE.swap(raw as *mut (), ::std::sync::atomic::Ordering::AcqRel);
+
+                if !previous.is_null() {
+                    drop(unsafe { ::std::boxed::Box::from_raw(previous as *mut BoxedClosure) });
+                }
+            }
+
+            /// Forwards its arguments to the currently stored closure.
+            ///
+            /// # Panics
+            /// Panics if no closure has been stored yet.
+            pub(super) unsafe extern "C" fn thunk($($arg: $arg_ty),*) -> $ret {
+                let raw = CLOSURE.load(::std::sync::atomic::Ordering::Acquire) as *mut BoxedClosure;
+                assert!(!raw.is_null(), "closure not set");
+
+                (*raw)($($arg),*)
+            }
+        }
+
+        $trampoline::store(::std::boxed::Box::new(move |$($arg: $arg_ty),*| -> $ret $body));
+
+        install_hook($target as $t, $trampoline::thunk as $t).map(|hook| {
+            $trampoline.store(hook.trampoline());
+            hook
+        })
+    }};
+}
+
 /// Declares a static variable that can be used to store a trampoline function pointer. Typically
 /// used in conjunction with [`hook`].
+///
+/// Backed by [`Trampoline`], a thread-safe `AtomicPtr`-based cell, rather than a `static mut`, so
+/// reading the trampoline from a hooked function running on another thread while it is being
+/// installed is sound.
 #[macro_export]
 macro_rules! make_trampoline {
     ($t:ty, $name:ident) => {
-        static mut $name: Option<$t> = None;
+        static $name: $crate::Trampoline<$t> = $crate::Trampoline::new();
     };
 }
 
@@ -110,6 +233,6 @@ macro_rules! make_trampoline {
 #[macro_export]
 macro_rules! get_trampoline {
     ($name:ident) => {
-        $name.expect("trampoline not set")
+        $name.load()
     };
 }
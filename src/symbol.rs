@@ -0,0 +1,29 @@
+use std::mem;
+
+use dobby_rs::Address;
+
+use crate::hook::Hook;
+use crate::{install_hook, Error};
+
+/// Resolves `symbol` in `module` (or the main executable/all loaded modules if `module` is
+/// `None`) and installs a hook redirecting it to `replacement`, exactly like [`install_hook`] but
+/// for targets that can only be named rather than linked against.
+///
+/// # Safety
+/// Hooking is inherently unsafe. It is up to the caller to ensure that `replacement`'s signature
+/// truly matches the resolved symbol's.
+pub unsafe fn install_hook_symbol<F: Copy>(
+    module: Option<&str>,
+    symbol: &str,
+    replacement: F,
+) -> Result<Hook<F>, Error> {
+    let address: Address =
+        dobby_rs::resolve_symbol(module, symbol).ok_or_else(|| Error::SymbolNotFound {
+            module: module.map(str::to_owned),
+            symbol: symbol.to_owned(),
+        })?;
+
+    let target: F = mem::transmute_copy(&address);
+
+    install_hook(target, replacement)
+}
@@ -0,0 +1,42 @@
+use std::marker::PhantomData;
+use std::mem;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+/// Thread-safe storage for a trampoline function pointer.
+///
+/// This backs [`make_trampoline!`](crate::make_trampoline) in place of a `static mut`: [`hook!`]
+/// writes the trampoline with a release store once hooking succeeds, and
+/// [`get_trampoline!`](crate::get_trampoline) reads it back with an acquire load, so a hooked
+/// function invoked concurrently with installation on another thread always observes either
+/// nothing (and panics) or a fully-initialized trampoline, never a half-written one.
+pub struct Trampoline<F: Copy> {
+    ptr: AtomicPtr<()>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Copy> Trampoline<F> {
+    /// Creates an empty trampoline slot.
+    pub const fn new() -> Self {
+        Self {
+            ptr: AtomicPtr::new(std::ptr::null_mut()),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Stores `value`, making it visible to any thread that subsequently calls [`Trampoline::load`].
+    pub fn store(&self, value: F) {
+        let raw: *mut () = unsafe { mem::transmute_copy(&value) };
+        self.ptr.store(raw, Ordering::Release);
+    }
+
+    /// Loads the stored trampoline.
+    ///
+    /// # Panics
+    /// Panics if no trampoline has been stored yet.
+    pub fn load(&self) -> F {
+        let raw = self.ptr.load(Ordering::Acquire);
+        assert!(!raw.is_null(), "trampoline not set");
+
+        unsafe { mem::transmute_copy(&raw) }
+    }
+}